@@ -1,7 +1,10 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::mem::MaybeUninit;
 use std::ptr;
 use thiserror::Error;
+use x11::xft;
 use x11::xlib;
 
 /// XError holds the X11 error message
@@ -26,11 +29,194 @@ enum Data {
     FontSet {
         display: *mut xlib::Display,
         fontset: xlib::XFontSet,
+        owned: bool,
     },
     XFont {
         display: *mut xlib::Display,
         xfont: *mut xlib::XFontStruct,
+        owned: bool,
     },
+    Xft {
+        display: *mut xlib::Display,
+        xftfont: *mut xft::XftFont,
+        owned: bool,
+    },
+    Fallbacks {
+        display: *mut xlib::Display,
+        fonts: Vec<ChainFont>,
+        owned: bool,
+    },
+}
+
+/// A single font loaded as part of a [`Context::with_fallbacks`] chain.
+///
+/// The variants mirror the two single-font backends so the chain can mix
+/// legacy core fonts with modern Xft ones.
+enum ChainFont {
+    XFont(*mut xlib::XFontStruct),
+    Xft(*mut xft::XftFont),
+}
+
+impl ChainFont {
+    /// Returns whether this font actually provides a glyph for `c`.
+    ///
+    /// SAFE as long as `display` is the display the font was loaded on.
+    unsafe fn contains(&self, display: *mut xlib::Display, c: char) -> bool {
+        match *self {
+            ChainFont::XFont(xfont) => core_contains(xfont, c),
+            ChainFont::Xft(xftfont) => xft::XftCharExists(display, xftfont, c as u32) != 0,
+        }
+    }
+
+    /// Returns the horizontal advance of `c` when drawn with this font.
+    ///
+    /// SAFE as long as `display` is the display the font was loaded on.
+    unsafe fn advance(&self, display: *mut xlib::Display, c: char) -> u64 {
+        match *self {
+            ChainFont::XFont(xfont) => core_advance(xfont, c),
+            ChainFont::Xft(xftfont) => {
+                let mut buf = [0u8; 4];
+                let encoded = c.encode_utf8(&mut buf);
+                let mut extents = MaybeUninit::uninit();
+                xft::XftTextExtentsUtf8(
+                    display,
+                    xftfont,
+                    encoded.as_ptr(),
+                    encoded.len() as i32,
+                    extents.as_mut_ptr(),
+                );
+                extents.assume_init().xOff as u64
+            }
+        }
+    }
+
+    /// Returns the ascent of this font.
+    ///
+    /// SAFE as long as the font pointer is still valid.
+    unsafe fn ascent(&self) -> i64 {
+        match *self {
+            ChainFont::XFont(xfont) => (*xfont).ascent as i64,
+            ChainFont::Xft(xftfont) => (*xftfont).ascent as i64,
+        }
+    }
+
+    /// Returns the descent of this font.
+    ///
+    /// SAFE as long as the font pointer is still valid.
+    unsafe fn descent(&self) -> i64 {
+        match *self {
+            ChainFont::XFont(xfont) => (*xfont).descent as i64,
+            ChainFont::Xft(xftfont) => (*xftfont).descent as i64,
+        }
+    }
+}
+
+/// Returns whether the core font `xfont` has a glyph for `c`.
+///
+/// The codepoint is split into a high/low byte pair and matched against the
+/// font's `byte1`/`byte2` ranges, so both single-byte (Latin) and 16-bit (CJK)
+/// core fonts are handled; a char outside those bounds, above the 16-bit range,
+/// or one whose per-char metrics are empty counts as missing so the chain can
+/// move on to the next font.
+unsafe fn core_contains(xfont: *mut xlib::XFontStruct, c: char) -> bool {
+    let font = &*xfont;
+    let code = c as u32;
+    // Core fonts address glyphs with an `XChar2b` (a byte1/byte2 pair), so no
+    // font can encode a codepoint beyond the 16-bit range.
+    if code > 0xffff {
+        return false;
+    }
+    let byte1 = code >> 8;
+    let byte2 = code & 0xff;
+    let columns = font.max_char_or_byte2 - font.min_char_or_byte2 + 1;
+    let index = if font.min_byte1 == 0 && font.max_byte1 == 0 {
+        // Single-byte font: the whole codepoint indexes the second-byte range.
+        if code < font.min_char_or_byte2 || code > font.max_char_or_byte2 {
+            return false;
+        }
+        code - font.min_char_or_byte2
+    } else {
+        // Two-byte font: index into the byte1 × byte2 matrix.
+        if byte1 < font.min_byte1
+            || byte1 > font.max_byte1
+            || byte2 < font.min_char_or_byte2
+            || byte2 > font.max_char_or_byte2
+        {
+            return false;
+        }
+        (byte1 - font.min_byte1) * columns + (byte2 - font.min_char_or_byte2)
+    };
+    if font.per_char.is_null() {
+        // Every char in the advertised range is present.
+        return true;
+    }
+    let cs = &*font.per_char.offset(index as isize);
+    cs.width != 0 || cs.ascent != 0 || cs.descent != 0 || cs.lbearing != 0 || cs.rbearing != 0
+}
+
+/// Returns the advance of `c` when drawn with the core font `xfont`.
+///
+/// This mirrors [`core_contains`]: single-byte fonts are measured by the
+/// font-encoded low byte through `XTextWidth`, while 16-bit fonts are measured
+/// by the `XChar2b` byte pair through `XTextWidth16`, so the width matches the
+/// glyph that coverage was reported for.
+unsafe fn core_advance(xfont: *mut xlib::XFontStruct, c: char) -> u64 {
+    let font = &*xfont;
+    let code = c as u32;
+    if font.min_byte1 == 0 && font.max_byte1 == 0 {
+        // Single-byte font: the low byte is the font-encoded glyph index.
+        let byte = [code as u8];
+        xlib::XTextWidth(xfont, byte.as_ptr() as *const _, 1) as u64
+    } else {
+        // Two-byte font: measure through an XChar2b pair.
+        let ch = xlib::XChar2b {
+            byte1: (code >> 8) as u8,
+            byte2: (code & 0xff) as u8,
+        };
+        xlib::XTextWidth16(xfont, &ch, 1) as u64
+    }
+}
+
+/// Frees every font in a fallback chain without touching the display.
+fn free_chain(display: *mut xlib::Display, fonts: &[ChainFont]) {
+    unsafe {
+        for font in fonts {
+            match *font {
+                ChainFont::XFont(xfont) => {
+                    xlib::XFreeFont(display, xfont);
+                }
+                ChainFont::Xft(xftfont) => {
+                    xft::XftFontClose(display, xftfont);
+                }
+            }
+        }
+    }
+}
+
+/// The measured extents of a piece of text.
+///
+/// `width`/`height` give the size of the logical box the text occupies, while
+/// `ascent`/`descent` give the baseline position needed for vertical
+/// alignment. The origins locate the top-left of the ink (the actual drawn
+/// pixels) and logical (advance) rectangles relative to the drawing origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextExtents {
+    /// Logical width (the horizontal advance) of the text.
+    pub width: u64,
+    /// Logical height (`ascent + descent`) of the text.
+    pub height: u64,
+    /// Distance from the baseline to the top of the text.
+    pub ascent: i64,
+    /// Distance from the baseline to the bottom of the text.
+    pub descent: i64,
+    /// X origin of the ink rectangle relative to the drawing origin.
+    pub ink_x: i64,
+    /// Y origin of the ink rectangle relative to the drawing origin.
+    pub ink_y: i64,
+    /// X origin of the logical rectangle relative to the drawing origin.
+    pub logical_x: i64,
+    /// Y origin of the logical rectangle relative to the drawing origin.
+    pub logical_y: i64,
 }
 
 /// A context, holding the internal data required to query a string
@@ -42,7 +228,7 @@ impl Context {
     /// Creates a new context given by the font string given here.
     ///
     /// The font string should be of the X11 form, as selected by `fontsel`.
-    /// XFT is not supported!
+    /// For Xft/fontconfig patterns use [`Context::new_xft`] instead.
     pub fn new(name: &str) -> Result<Self, XError> {
         let name: CString = CString::new(name)?;
         // SAFE because we simply call the
@@ -74,6 +260,7 @@ impl Context {
                 data: Data::FontSet {
                     display: dpy,
                     fontset,
+                    owned: true,
                 },
             })
         } else {
@@ -88,12 +275,220 @@ impl Context {
                     data: Data::XFont {
                         display: dpy,
                         xfont,
+                        owned: true,
                     },
                 })
             }
         }
     }
 
+    /// Creates a new context using the Xft/fontconfig font matching `name`.
+    ///
+    /// Unlike [`Context::new`], `name` is a fontconfig pattern such as
+    /// `"Sans:size=10.5"`, letting you measure modern scalable fonts.
+    pub fn new_xft(name: &str) -> Result<Self, XError> {
+        let name: CString = CString::new(name)?;
+        // SAFE because we simply open the default display
+        let dpy = unsafe { xlib::XOpenDisplay(ptr::null()) };
+        if dpy.is_null() {
+            return Err(XError::DisplayOpen);
+        }
+        // SAFE as dpy is a valid display
+        let screen = unsafe { xlib::XDefaultScreen(dpy) };
+        // SAFE as dpy, screen and name are all valid
+        let xftfont = unsafe { xft::XftFontOpenName(dpy, screen, name.as_ptr()) };
+        if xftfont.is_null() {
+            // SAFE as dpy is a valid display
+            unsafe { xlib::XCloseDisplay(dpy) };
+            Err(XError::CouldNotLoadFont(name))
+        } else {
+            Ok(Context {
+                data: Data::Xft {
+                    display: dpy,
+                    xftfont,
+                    owned: true,
+                },
+            })
+        }
+    }
+
+    /// Creates a new context on an existing, borrowed display connection.
+    ///
+    /// Applications that already hold an xlib `Display` — status bars, window
+    /// managers — can share it instead of opening a second connection. The
+    /// returned context does not own `dpy`, so [`Drop`] frees only the loaded
+    /// font and leaves the connection open for the caller to close.
+    ///
+    /// # Safety
+    ///
+    /// `dpy` must be a valid display that outlives the returned [`Context`].
+    pub unsafe fn from_display(dpy: *mut xlib::Display, name: &str) -> Result<Self, XError> {
+        let name: CString = CString::new(name)?;
+        let mut missing_ptr = MaybeUninit::uninit();
+        let mut missing_len = MaybeUninit::uninit();
+        // SAFE because values are correct
+        let fontset = unsafe {
+            xlib::XCreateFontSet(
+                dpy,
+                name.as_ptr(),
+                missing_ptr.as_mut_ptr(),
+                missing_len.as_mut_ptr(),
+                ptr::null_mut(),
+            )
+        };
+
+        // SAFE because XCreateFontSet always sets both ptrs to NULL or a valid value
+        unsafe {
+            if !missing_ptr.assume_init().is_null() {
+                xlib::XFreeStringList(missing_ptr.assume_init());
+            }
+        }
+        if !fontset.is_null() {
+            Ok(Context {
+                data: Data::FontSet {
+                    display: dpy,
+                    fontset,
+                    owned: false,
+                },
+            })
+        } else {
+            // SAFE as both dpy and name are valid
+            let xfont = unsafe { xlib::XLoadQueryFont(dpy, name.as_ptr()) };
+            if xfont.is_null() {
+                Err(XError::CouldNotLoadFont(name))
+            } else {
+                Ok(Context {
+                    data: Data::XFont {
+                        display: dpy,
+                        xfont,
+                        owned: false,
+                    },
+                })
+            }
+        }
+    }
+
+    /// Creates a new context from a priority-ordered list of fonts.
+    ///
+    /// When measuring, each codepoint is attributed to the first font in
+    /// `names` that actually contains its glyph, so mixed-script text (CJK,
+    /// emoji, symbols) is measured correctly instead of silently collapsing to
+    /// zero. Codepoints that no font covers fall back to the last font in the
+    /// list. Each name is loaded as a core font, or — failing that — as an Xft
+    /// font, so legacy XLFD names and fontconfig patterns can be mixed freely.
+    pub fn with_fallbacks(names: &[&str]) -> Result<Self, XError> {
+        // SAFE because we simply open the default display
+        let dpy = unsafe { xlib::XOpenDisplay(ptr::null()) };
+        if dpy.is_null() {
+            return Err(XError::DisplayOpen);
+        }
+        let screen = unsafe { xlib::XDefaultScreen(dpy) };
+        let mut fonts = Vec::with_capacity(names.len());
+        for name in names {
+            let name: CString = match CString::new(*name) {
+                Ok(name) => name,
+                Err(err) => {
+                    free_chain(dpy, &fonts);
+                    unsafe { xlib::XCloseDisplay(dpy) };
+                    return Err(err.into());
+                }
+            };
+            // SAFE as dpy and name are valid
+            let xfont = unsafe { xlib::XLoadQueryFont(dpy, name.as_ptr()) };
+            if !xfont.is_null() {
+                fonts.push(ChainFont::XFont(xfont));
+                continue;
+            }
+            // SAFE as dpy, screen and name are valid
+            let xftfont = unsafe { xft::XftFontOpenName(dpy, screen, name.as_ptr()) };
+            if !xftfont.is_null() {
+                fonts.push(ChainFont::Xft(xftfont));
+                continue;
+            }
+            free_chain(dpy, &fonts);
+            unsafe { xlib::XCloseDisplay(dpy) };
+            return Err(XError::CouldNotLoadFont(name));
+        }
+        Ok(Context {
+            data: Data::Fallbacks {
+                display: dpy,
+                fonts,
+                owned: true,
+            },
+        })
+    }
+
+    /// Creates a new context from a scalable core font at a requested size.
+    ///
+    /// A scalable XLFD leaves its pixel-size, point-size and average-width
+    /// fields (the 7th, 8th and 12th `-`-separated fields) set to `"0"`. When
+    /// `name` is such a font, it is rewritten to request `size` pixels — the
+    /// point-size becomes `size * 10`, the resolution fields are filled with
+    /// the display's actual DPI, and the average width is replaced by `"*"` — then
+    /// loaded. Non-scalable names are loaded verbatim, matching
+    /// [`Context::new`].
+    pub fn new_sized(name: &str, size: u32) -> Result<Self, XError> {
+        let fields: Vec<&str> = name.split('-').collect();
+        // A valid XLFD starts with a '-', so `split` yields a leading empty
+        // field followed by the 14 real fields.
+        let scalable = fields.len() == 15
+            && fields[7] == "0"
+            && fields[8] == "0"
+            && fields[12] == "0";
+        if !scalable {
+            return Self::new(name);
+        }
+        // SAFE because we simply open the default display
+        let dpy = unsafe { xlib::XOpenDisplay(ptr::null()) };
+        if dpy.is_null() {
+            return Err(XError::DisplayOpen);
+        }
+        // SAFE as dpy is a valid display
+        let (resx, resy) = unsafe {
+            let screen = xlib::XDefaultScreen(dpy);
+            let resx = (xlib::XDisplayWidth(dpy, screen) as f64 * 25.4
+                / xlib::XDisplayWidthMM(dpy, screen) as f64)
+                .round() as i64;
+            let resy = (xlib::XDisplayHeight(dpy, screen) as f64 * 25.4
+                / xlib::XDisplayHeightMM(dpy, screen) as f64)
+                .round() as i64;
+            (resx, resy)
+        };
+        let pixel = size.to_string();
+        let point = (size * 10).to_string();
+        let resx = resx.to_string();
+        let resy = resy.to_string();
+        let mut rewritten = fields;
+        rewritten[7] = pixel.as_str();
+        rewritten[8] = point.as_str();
+        rewritten[9] = resx.as_str();
+        rewritten[10] = resy.as_str();
+        rewritten[12] = "*";
+        let name: CString = match CString::new(rewritten.join("-")) {
+            Ok(name) => name,
+            Err(err) => {
+                // SAFE as dpy is a valid display
+                unsafe { xlib::XCloseDisplay(dpy) };
+                return Err(err.into());
+            }
+        };
+        // SAFE as dpy and name are valid
+        let xfont = unsafe { xlib::XLoadQueryFont(dpy, name.as_ptr()) };
+        if xfont.is_null() {
+            // SAFE as dpy is a valid display
+            unsafe { xlib::XCloseDisplay(dpy) };
+            Err(XError::CouldNotLoadFont(name))
+        } else {
+            Ok(Context {
+                data: Data::XFont {
+                    display: dpy,
+                    xfont,
+                    owned: true,
+                },
+            })
+        }
+    }
+
     /// Creates a new context with the misc-fixed font.
     pub fn with_misc() -> Result<Self, XError> {
         Self::new("-misc-fixed-*-*-*-*-*-*-*-*-*-*-*-*")
@@ -101,7 +496,16 @@ impl Context {
 
     /// Get text width for the given string
     pub fn text_width<S: AsRef<str>>(&self, text: S) -> Result<u64, XError> {
-        get_text_width(&self, text)
+        get_text_width(self, text)
+    }
+
+    /// Get the full extents of the given string.
+    ///
+    /// Unlike [`Context::text_width`], this also reports the ascent, descent
+    /// and bounding rectangles, which callers laying out multi-line or
+    /// vertically-centered text need.
+    pub fn text_extents<S: AsRef<str>>(&self, text: S) -> Result<TextExtents, XError> {
+        get_text_extents(self, text)
     }
 }
 
@@ -109,13 +513,45 @@ impl Drop for Context {
     fn drop(&mut self) {
         unsafe {
             match self.data {
-                Data::FontSet { display, fontset } => {
+                Data::FontSet {
+                    display,
+                    fontset,
+                    owned,
+                } => {
                     xlib::XFreeFontSet(display, fontset);
-                    xlib::XCloseDisplay(display);
+                    if owned {
+                        xlib::XCloseDisplay(display);
+                    }
                 }
-                Data::XFont { display, xfont } => {
+                Data::XFont {
+                    display,
+                    xfont,
+                    owned,
+                } => {
                     xlib::XFreeFont(display, xfont);
-                    xlib::XCloseDisplay(display);
+                    if owned {
+                        xlib::XCloseDisplay(display);
+                    }
+                }
+                Data::Xft {
+                    display,
+                    xftfont,
+                    owned,
+                } => {
+                    xft::XftFontClose(display, xftfont);
+                    if owned {
+                        xlib::XCloseDisplay(display);
+                    }
+                }
+                Data::Fallbacks {
+                    display,
+                    ref fonts,
+                    owned,
+                } => {
+                    free_chain(display, fonts);
+                    if owned {
+                        xlib::XCloseDisplay(display);
+                    }
                 }
             }
         }
@@ -124,7 +560,8 @@ impl Drop for Context {
 
 /// Get the width of the text rendered with the font specified by the context
 pub fn get_text_width<S: AsRef<str>>(ctx: &Context, text: S) -> Result<u64, XError> {
-    let text = CString::new(text.as_ref())?;
+    let raw = text.as_ref();
+    let text = CString::new(raw)?;
     unsafe {
         match ctx.data {
             Data::FontSet { fontset, .. } => {
@@ -141,7 +578,220 @@ pub fn get_text_width<S: AsRef<str>>(ctx: &Context, text: S) -> Result<u64, XErr
             Data::XFont { xfont, .. } => {
                 Ok(xlib::XTextWidth(xfont, text.as_ptr(), text.as_bytes().len() as i32) as u64)
             }
+            Data::Xft {
+                display, xftfont, ..
+            } => {
+                let bytes = text.as_bytes();
+                let mut extents = MaybeUninit::uninit();
+                xft::XftTextExtentsUtf8(
+                    display,
+                    xftfont,
+                    bytes.as_ptr(),
+                    bytes.len() as i32,
+                    extents.as_mut_ptr(),
+                );
+                Ok(extents.assume_init().xOff as u64)
+            }
+            Data::Fallbacks {
+                display,
+                ref fonts,
+                ..
+            } => {
+                let mut width: u64 = 0;
+                for c in raw.chars() {
+                    let chosen = fonts
+                        .iter()
+                        .find(|font| font.contains(display, c))
+                        .or_else(|| fonts.last());
+                    if let Some(font) = chosen {
+                        width += font.advance(display, c);
+                    }
+                }
+                Ok(width)
+            }
+        }
+    }
+}
+
+/// Get the full extents of the text rendered with the font specified by the context
+pub fn get_text_extents<S: AsRef<str>>(ctx: &Context, text: S) -> Result<TextExtents, XError> {
+    let raw = text.as_ref();
+    let text = CString::new(raw)?;
+    unsafe {
+        match ctx.data {
+            Data::FontSet { fontset, .. } => {
+                let mut ink = MaybeUninit::uninit();
+                let mut logical = MaybeUninit::uninit();
+                xlib::XmbTextExtents(
+                    fontset,
+                    text.as_ptr(),
+                    text.as_bytes().len() as i32,
+                    ink.as_mut_ptr(),
+                    logical.as_mut_ptr(),
+                );
+                let ink = ink.assume_init();
+                let logical = logical.assume_init();
+                // The font set's max logical extent yields its overall ascent
+                // and descent, which XmbTextExtents does not report directly.
+                let set_extents = xlib::XExtentsOfFontSet(fontset);
+                let ascent = -((*set_extents).max_logical_extent.y as i64);
+                let descent = (*set_extents).max_logical_extent.y as i64
+                    + (*set_extents).max_logical_extent.height as i64;
+                Ok(TextExtents {
+                    width: logical.width as u64,
+                    height: (ascent + descent) as u64,
+                    ascent,
+                    descent,
+                    ink_x: ink.x as i64,
+                    ink_y: ink.y as i64,
+                    logical_x: logical.x as i64,
+                    logical_y: logical.y as i64,
+                })
+            }
+            Data::XFont { xfont, .. } => {
+                let mut direction = MaybeUninit::uninit();
+                let mut font_ascent = MaybeUninit::uninit();
+                let mut font_descent = MaybeUninit::uninit();
+                let mut overall = MaybeUninit::uninit();
+                xlib::XTextExtents(
+                    xfont,
+                    text.as_ptr(),
+                    text.as_bytes().len() as i32,
+                    direction.as_mut_ptr(),
+                    font_ascent.as_mut_ptr(),
+                    font_descent.as_mut_ptr(),
+                    overall.as_mut_ptr(),
+                );
+                let ascent = font_ascent.assume_init() as i64;
+                let descent = font_descent.assume_init() as i64;
+                let overall = overall.assume_init();
+                Ok(TextExtents {
+                    width: overall.width as u64,
+                    height: (ascent + descent) as u64,
+                    ascent,
+                    descent,
+                    ink_x: overall.lbearing as i64,
+                    ink_y: -(overall.ascent as i64),
+                    logical_x: 0,
+                    logical_y: -ascent,
+                })
+            }
+            Data::Xft {
+                display, xftfont, ..
+            } => {
+                let bytes = text.as_bytes();
+                let mut glyph = MaybeUninit::uninit();
+                xft::XftTextExtentsUtf8(
+                    display,
+                    xftfont,
+                    bytes.as_ptr(),
+                    bytes.len() as i32,
+                    glyph.as_mut_ptr(),
+                );
+                let glyph = glyph.assume_init();
+                let ascent = (*xftfont).ascent as i64;
+                let descent = (*xftfont).descent as i64;
+                Ok(TextExtents {
+                    width: glyph.xOff as u64,
+                    height: (ascent + descent) as u64,
+                    ascent,
+                    descent,
+                    ink_x: -(glyph.x as i64),
+                    ink_y: -(glyph.y as i64),
+                    logical_x: 0,
+                    logical_y: -ascent,
+                })
+            }
+            Data::Fallbacks {
+                display,
+                ref fonts,
+                ..
+            } => {
+                let mut width: u64 = 0;
+                let mut ascent: i64 = 0;
+                let mut descent: i64 = 0;
+                for c in raw.chars() {
+                    let chosen = fonts
+                        .iter()
+                        .find(|font| font.contains(display, c))
+                        .or_else(|| fonts.last());
+                    if let Some(font) = chosen {
+                        width += font.advance(display, c);
+                        ascent = ascent.max(font.ascent());
+                        descent = descent.max(font.descent());
+                    }
+                }
+                Ok(TextExtents {
+                    width,
+                    height: (ascent + descent) as u64,
+                    ascent,
+                    descent,
+                    ink_x: 0,
+                    ink_y: -ascent,
+                    logical_x: 0,
+                    logical_y: -ascent,
+                })
+            }
+        }
+    }
+}
+
+/// A [`Context`] wrapper that memoizes text widths per string.
+///
+/// Redraw-heavy callers (status bars, menus) measure the same labels on every
+/// frame; this caches the result so repeat queries avoid a round-trip or glyph
+/// walk. The cache is bounded: once it holds `capacity` entries it is cleared
+/// before the next insert so long-running processes do not grow unbounded.
+pub struct CachedContext {
+    context: Context,
+    cache: RefCell<HashMap<String, u64>>,
+    capacity: usize,
+}
+
+impl CachedContext {
+    /// The number of entries a cache created with [`CachedContext::new`] holds.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Wraps a context with a cache of the default capacity.
+    pub fn new(context: Context) -> Self {
+        Self::with_capacity(context, Self::DEFAULT_CAPACITY)
+    }
+
+    /// Wraps a context with a cache bounded to `capacity` entries.
+    pub fn with_capacity(context: Context, capacity: usize) -> Self {
+        CachedContext {
+            context,
+            cache: RefCell::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Get text width for the given string, returning a cached result on a hit.
+    ///
+    /// On a miss the width is computed through the wrapped [`Context`] and
+    /// stored for subsequent queries.
+    pub fn text_width<S: AsRef<str>>(&self, text: S) -> Result<u64, XError> {
+        let text = text.as_ref();
+        if let Some(width) = self.cache.borrow().get(text) {
+            return Ok(*width);
+        }
+        let width = self.context.text_width(text)?;
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= self.capacity {
+            cache.clear();
         }
+        cache.insert(text.to_owned(), width);
+        Ok(width)
+    }
+
+    /// Empties the cache, for example after the font or display changes.
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Returns a reference to the wrapped context.
+    pub fn context(&self) -> &Context {
+        &self.context
     }
 }
 
@@ -157,7 +807,7 @@ pub fn setup_multithreading() {
 
 #[cfg(test)]
 mod test {
-    use super::{get_text_width, Context};
+    use super::{get_text_width, CachedContext, Context};
     use std::sync::Once;
     use x11::xlib;
     static SETUP: Once = Once::new();
@@ -176,9 +826,8 @@ mod test {
     #[test]
     fn test_context_drop() {
         setup();
-        let ctx = Context::with_misc();
+        let ctx = Context::with_misc().unwrap();
         drop(ctx);
-        assert!(true);
     }
     #[test]
     fn test_text_width() {
@@ -192,4 +841,49 @@ mod test {
         let ctx = Context::new("?");
         assert!(ctx.is_err());
     }
+    #[test]
+    fn test_text_width_cached() {
+        setup();
+        let ctx = CachedContext::new(Context::with_misc().unwrap());
+        let first = ctx.text_width("Hello World").unwrap();
+        assert!(first > 0);
+        // A repeated query must return the same, now cached, width.
+        assert_eq!(first, ctx.text_width("Hello World").unwrap());
+        ctx.clear_cache();
+        assert_eq!(first, ctx.text_width("Hello World").unwrap());
+    }
+    #[test]
+    fn test_new_xft() {
+        setup();
+        // Xft resolves fontconfig patterns, so a generic family must load and
+        // measure to a positive width.
+        let ctx = Context::new_xft("monospace").unwrap();
+        assert!(ctx.text_width("Hello World").unwrap() > 0);
+    }
+    #[test]
+    fn test_new_sized_non_scalable_passthrough() {
+        setup();
+        // The misc-fixed pattern has wildcard, not "0", size fields, so it is
+        // not scalable and new_sized must route straight through to new.
+        let ctx = Context::new_sized("-misc-fixed-*-*-*-*-*-*-*-*-*-*-*-*", 14);
+        assert!(ctx.is_ok());
+    }
+    #[test]
+    fn test_text_extents() {
+        setup();
+        let ctx = Context::with_misc().unwrap();
+        let extents = ctx.text_extents("Hello World").unwrap();
+        // The logical width must agree with text_width, and the box must have
+        // a real height split across the baseline.
+        assert_eq!(extents.width, ctx.text_width("Hello World").unwrap());
+        assert!(extents.ascent > 0);
+        assert_eq!(extents.height, (extents.ascent + extents.descent) as u64);
+    }
+    #[test]
+    fn test_with_fallbacks_ascii() {
+        setup();
+        let ctx = Context::with_fallbacks(&["-misc-fixed-*-*-*-*-*-*-*-*-*-*-*-*"]).unwrap();
+        // A single-font chain must still measure plain ASCII with a positive width.
+        assert!(ctx.text_width("Hello World").unwrap() > 0);
+    }
 }